@@ -19,16 +19,24 @@ pub(crate) const DEFAULT_PROFILE_NAME: &str = "default";
 
 #[derive(Debug, Default)]
 pub struct Profiles {
+    /// The profile selected with [`Self::set_active_profile`], persisted across invocations.
+    /// Overridden at runtime by an explicit CLI flag or the `STARKLI_PROFILE` environment variable.
+    pub active_profile: Option<String>,
     pub profiles: IndexMap<String, Profile>,
 }
 
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct Profile {
+    /// The name of another profile whose networks this profile inherits. Networks defined
+    /// directly on this profile take precedence over those inherited from the parent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extends: Option<String>,
+    #[serde(default)]
     pub networks: IndexMap<String, Network>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct Network {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -43,21 +51,48 @@ pub struct Network {
     pub provider: NetworkProvider,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum NetworkProvider {
-    Rpc(Url),
-    Free(FreeProviderVendor),
+    /// An RPC URL, possibly containing `${VAR}` / `${VAR:-default}` placeholders. Kept as a raw
+    /// string instead of a parsed [`Url`] since placeholders are only expanded in
+    /// [`NetworkProvider::resolve`]; parsing it eagerly here would percent-encode `{`/`}` in the
+    /// path and corrupt any placeholder before it could be substituted.
+    Rpc(String),
+    Free {
+        vendor: FreeProviderVendor,
+        /// API key for the vendor's endpoint, possibly containing a `${VAR}` placeholder. Kept
+        /// unexpanded for the same reason as [`NetworkProvider::Rpc`].
+        api_key: Option<String>,
+    },
+    /// A provider `type` not recognized by this build of starkli. Kept around so that a
+    /// `profiles.toml` written by a newer version doesn't fail to load entirely; this only turns
+    /// into an error once the network is actually selected for use.
+    Unknown {
+        raw_type: String,
+        /// Any fields on this provider's table other than `type`, preserved verbatim (whatever
+        /// shape they are) so they round-trip back out on save and an older binary doesn't lose
+        /// a newer provider's settings just by loading and re-saving the config.
+        extra: IndexMap<String, toml::Value>,
+    },
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
-#[serde(deny_unknown_fields, rename_all = "snake_case")]
+/// A [`NetworkProvider`] with all `${VAR}` placeholders expanded and, for free vendors, the
+/// concrete authenticated endpoint resolved. This is what's actually used to talk to a node.
+#[derive(Debug, Clone)]
+pub struct ResolvedProvider {
+    pub url: Url,
+}
+
+#[derive(Debug, Clone)]
 pub enum FreeProviderVendor {
     Blast,
     Nethermind,
+    /// A `vendor` not recognized by this build of starkli. See [`NetworkProvider::Unknown`].
+    Unknown(String),
 }
 
 struct ChainIdVisitor;
-struct UrlVisitor;
+struct FreeProviderVendorVisitor;
 
 impl Profiles {
     pub fn load() -> Result<Self> {
@@ -73,48 +108,288 @@ impl Profiles {
             Self::default()
         };
 
-        // Custom profile to be supported in the future
-        if loaded_profiles.profiles.len() > 1
-            || (loaded_profiles.profiles.len() == 1
-                && !loaded_profiles.profiles.contains_key(DEFAULT_PROFILE_NAME))
-        {
+        // Every profile must resolve (no missing/cyclic `extends`), and the chain IDs of its
+        // fully-inherited network set must be unique.
+        for profile_name in loaded_profiles.profiles.keys() {
+            loaded_profiles.validate_profile(profile_name)?;
+        }
+
+        Ok(loaded_profiles)
+    }
+
+    /// Resolves the profile to use, picking (in order of priority) the explicitly requested
+    /// `name`, the `STARKLI_PROFILE` environment variable, the persisted [`Self::active_profile`],
+    /// or the `default` profile; then merges in any networks inherited through `extends`.
+    pub fn get_active(&self, name: Option<&str>) -> Result<Profile> {
+        let name = match name {
+            Some(name) => name.to_owned(),
+            None => std::env::var("STARKLI_PROFILE").ok().unwrap_or_else(|| {
+                self.active_profile
+                    .clone()
+                    .unwrap_or_else(|| DEFAULT_PROFILE_NAME.to_owned())
+            }),
+        };
+
+        if name == DEFAULT_PROFILE_NAME && !self.profiles.contains_key(DEFAULT_PROFILE_NAME) {
+            return Ok(Profile::default());
+        }
+
+        self.resolve_profile(&name, &mut Vec::new())
+    }
+
+    /// Inserts or replaces a network in `profile_name`, creating the profile if it doesn't exist
+    /// yet, and re-validates chain ID uniqueness for every profile whose resolved network set
+    /// includes `profile_name`'s — i.e. `profile_name` itself, plus any profile that inherits it
+    /// (directly or transitively) through `extends`. On validation failure (e.g. a duplicate
+    /// chain ID, including one only introduced into a profile that extends `profile_name`),
+    /// `self` is left exactly as it was before the call — the rejected network is rolled back
+    /// rather than left dangling in memory, where a later `save()` could write out a config that
+    /// the next `load()` can't parse.
+    pub fn upsert_network(
+        &mut self,
+        profile_name: &str,
+        network_id: impl Into<String>,
+        network: Network,
+    ) -> Result<()> {
+        let network_id = network_id.into();
+        let profile_existed = self.profiles.contains_key(profile_name);
+        let previous_network = self
+            .profiles
+            .get(profile_name)
+            .and_then(|profile| profile.networks.get(&network_id))
+            .cloned();
+
+        self.profiles
+            .entry(profile_name.to_owned())
+            .or_default()
+            .networks
+            .insert(network_id.clone(), network);
+
+        let dependents = self.profiles_depending_on(profile_name);
+        if let Err(err) = dependents.iter().try_for_each(|name| self.validate_profile(name)) {
+            if profile_existed {
+                let profile = self
+                    .profiles
+                    .get_mut(profile_name)
+                    .expect("profile was just observed to exist");
+
+                match previous_network {
+                    Some(previous) => {
+                        profile.networks.insert(network_id, previous);
+                    }
+                    None => {
+                        profile.networks.shift_remove(&network_id);
+                    }
+                }
+            } else {
+                self.profiles.shift_remove(profile_name);
+            }
+
+            return Err(err);
+        }
+
+        Ok(())
+    }
+
+    /// Removes a network from `profile_name`, returning it. Errors if the profile or network
+    /// doesn't exist.
+    pub fn remove_network(&mut self, profile_name: &str, network_id: &str) -> Result<Network> {
+        self.profiles
+            .get_mut(profile_name)
+            .ok_or_else(|| anyhow::anyhow!("profile `{}` not found", profile_name))?
+            .networks
+            .shift_remove(network_id)
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "network `{}` not found in profile `{}`",
+                    network_id,
+                    profile_name
+                )
+            })
+    }
+
+    /// Renames a network within `profile_name`, preserving its position in the map. Renaming a
+    /// network to its current name is a no-op success. Errors if the profile or the old network
+    /// doesn't exist, or if the new name is already taken by a different network.
+    pub fn rename_network(
+        &mut self,
+        profile_name: &str,
+        network_id: &str,
+        new_network_id: impl Into<String>,
+    ) -> Result<()> {
+        let new_network_id = new_network_id.into();
+
+        let profile = self
+            .profiles
+            .get_mut(profile_name)
+            .ok_or_else(|| anyhow::anyhow!("profile `{}` not found", profile_name))?;
+
+        if new_network_id != network_id && profile.networks.contains_key(&new_network_id) {
             anyhow::bail!(
-                "invalid profiles: only the `default` profile is supported at the moment"
+                "network `{}` already exists in profile `{}`",
+                new_network_id,
+                profile_name
             );
         }
 
-        if let Some(default_profile) = loaded_profiles.profiles.get(DEFAULT_PROFILE_NAME) {
-            // Checks chain ID duplication
-            let mut chain_id_last_used_in_network = HashMap::new();
-            for (network_id, network) in default_profile.networks.iter() {
-                match chain_id_last_used_in_network.entry(network.chain_id) {
-                    Entry::Occupied(entry) => anyhow::bail!(
-                        "invalid profile `default`: networks {} and {} have the same chain ID",
-                        entry.get(),
-                        network_id
-                    ),
-                    Entry::Vacant(entry) => {
-                        entry.insert(network_id);
-                    }
+        let (index, _, network) = profile
+            .networks
+            .shift_remove_full(network_id)
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "network `{}` not found in profile `{}`",
+                    network_id,
+                    profile_name
+                )
+            })?;
+        profile
+            .networks
+            .shift_insert(index, new_network_id, network);
+
+        Ok(())
+    }
+
+    /// Sets the persisted active profile, or clears it (falling back to `default`) when `None`.
+    /// Errors if a named profile doesn't exist.
+    pub fn set_active_profile(&mut self, name: Option<String>) -> Result<()> {
+        if let Some(name) = &name {
+            anyhow::ensure!(
+                self.profiles.contains_key(name),
+                "profile `{}` not found",
+                name
+            );
+        }
+
+        self.active_profile = name;
+
+        Ok(())
+    }
+
+    /// Checks that `profile_name` resolves (no missing/cyclic `extends`) and that the chain IDs
+    /// of its fully-inherited network set are unique.
+    fn validate_profile(&self, profile_name: &str) -> Result<()> {
+        self.resolve_profile(profile_name, &mut Vec::new())?;
+        Ok(())
+    }
+
+    /// The names of every profile whose resolved network set includes `profile_name`'s: that
+    /// profile itself, plus any profile that reaches it (directly or transitively) through
+    /// `extends`. A mutation to `profile_name`'s networks can only invalidate chain-ID uniqueness
+    /// for profiles in this set.
+    fn profiles_depending_on(&self, profile_name: &str) -> Vec<String> {
+        self.profiles
+            .keys()
+            .filter(|candidate| self.extends_chain_includes(candidate, profile_name))
+            .cloned()
+            .collect()
+    }
+
+    /// Whether following `start`'s `extends` chain (including `start` itself) ever reaches
+    /// `target`. A cycle in the chain is treated as not reaching `target`; `resolve_profile`
+    /// reports cycles on its own.
+    fn extends_chain_includes(&self, start: &str, target: &str) -> bool {
+        let mut current = start.to_owned();
+        let mut visited = Vec::new();
+
+        loop {
+            if current == target {
+                return true;
+            }
+            if visited.iter().any(|visited| visited == &current) {
+                return false;
+            }
+            visited.push(current.clone());
+
+            match self.profiles.get(&current).and_then(|p| p.extends.clone()) {
+                Some(parent) => current = parent,
+                None => return false,
+            }
+        }
+    }
+
+    /// Resolves a profile by name into its fully-merged form: `extends` chains are followed and
+    /// flattened, with networks defined directly on a profile overriding those inherited from its
+    /// parent. `visiting` tracks the chain of profile names followed so far, for cycle detection.
+    /// The chain IDs of the resulting merged network set are validated for uniqueness before
+    /// returning, so this check is enforced at every point of use (`load`, `get_active`,
+    /// `validate_profile`), not only when a profile happens to be the one last mutated.
+    fn resolve_profile(&self, name: &str, visiting: &mut Vec<String>) -> Result<Profile> {
+        if visiting.iter().any(|visited| visited == name) {
+            visiting.push(name.to_owned());
+            anyhow::bail!(
+                "profile inheritance cycle detected: {}",
+                visiting.join(" -> ")
+            );
+        }
+        visiting.push(name.to_owned());
+
+        let profile = self
+            .profiles
+            .get(name)
+            .ok_or_else(|| anyhow::anyhow!("profile `{}` not found", name))?;
+
+        let mut resolved = match &profile.extends {
+            Some(parent) => self.resolve_profile(parent, visiting)?,
+            None => Profile::default(),
+        };
+
+        for (network_id, network) in profile.networks.iter() {
+            resolved.networks.insert(network_id.clone(), network.clone());
+        }
+        resolved.extends = None;
+
+        Self::validate_chain_ids(name, &resolved.networks)?;
+
+        Ok(resolved)
+    }
+
+    fn validate_chain_ids(
+        profile_name: &str,
+        networks: &IndexMap<String, Network>,
+    ) -> Result<()> {
+        let mut chain_id_last_used_in_network = HashMap::new();
+        for (network_id, network) in networks.iter() {
+            match chain_id_last_used_in_network.entry(network.chain_id) {
+                Entry::Occupied(entry) => anyhow::bail!(
+                    "invalid profile `{}`: networks {} and {} have the same chain ID",
+                    profile_name,
+                    entry.get(),
+                    network_id
+                ),
+                Entry::Vacant(entry) => {
+                    entry.insert(network_id);
                 }
             }
         }
 
-        Ok(loaded_profiles)
+        Ok(())
     }
 
+    /// Serializes and writes `profiles.toml` atomically: the new contents are written to a
+    /// sibling temp file and then renamed into place, so a crash or power loss mid-write can't
+    /// leave behind a corrupt or truncated config file.
     pub fn save(&self) -> Result<()> {
         let serialized = toml::to_string_pretty(self)?;
 
         let config_folder = Self::get_config_folder()?;
         if !config_folder.exists() {
-            std::fs::create_dir_all(config_folder)?;
+            std::fs::create_dir_all(&config_folder)?;
         }
 
         let path = Self::get_profiles_path()?;
-        let mut file = std::fs::File::create(path)?;
+        let temp_path = config_folder.join(format!(
+            "{}.tmp",
+            path.file_name()
+                .expect("profiles path must have a file name")
+                .to_string_lossy()
+        ));
 
+        let mut file = std::fs::File::create(&temp_path)?;
         file.write_all(serialized.as_bytes())?;
+        file.sync_all()?;
+        drop(file);
+
+        std::fs::rename(&temp_path, &path)?;
 
         Ok(())
     }
@@ -140,10 +415,18 @@ impl Serialize for Profiles {
         S: serde::Serializer,
     {
         #[derive(Serialize)]
-        #[serde(transparent)]
-        struct Transparent<'a>(&'a IndexMap<String, Profile>);
+        struct Raw<'a> {
+            #[serde(skip_serializing_if = "Option::is_none")]
+            active_profile: &'a Option<String>,
+            #[serde(flatten)]
+            profiles: &'a IndexMap<String, Profile>,
+        }
 
-        Transparent(&self.profiles).serialize(serializer)
+        Raw {
+            active_profile: &self.active_profile,
+            profiles: &self.profiles,
+        }
+        .serialize(serializer)
     }
 }
 
@@ -153,11 +436,18 @@ impl<'de> Deserialize<'de> for Profiles {
         D: serde::Deserializer<'de>,
     {
         #[derive(Deserialize)]
-        #[serde(deny_unknown_fields, transparent)]
-        struct Transparent(IndexMap<String, Profile>);
+        struct Raw {
+            #[serde(default)]
+            active_profile: Option<String>,
+            #[serde(flatten)]
+            profiles: IndexMap<String, Profile>,
+        }
+
+        let raw = Raw::deserialize(deserializer)?;
 
         Ok(Self {
-            profiles: Transparent::deserialize(deserializer)?.0,
+            active_profile: raw.active_profile,
+            profiles: raw.profiles,
         })
     }
 }
@@ -176,13 +466,29 @@ impl Serialize for NetworkProvider {
         struct FreeVariant<'a> {
             r#type: &'static str,
             vendor: &'a FreeProviderVendor,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            api_key: &'a Option<String>,
+        }
+
+        #[derive(Serialize)]
+        struct UnknownVariant<'a> {
+            r#type: &'a str,
+            #[serde(flatten)]
+            extra: &'a IndexMap<String, toml::Value>,
         }
 
         match self {
-            Self::Rpc(value) => RpcVariant(value.as_ref()).serialize(serializer),
-            Self::Free(value) => FreeVariant {
+            Self::Rpc(value) => RpcVariant(value).serialize(serializer),
+            Self::Free { vendor, api_key } => FreeVariant {
                 r#type: "free",
-                vendor: value,
+                vendor,
+                api_key,
+            }
+            .serialize(serializer),
+            // Round-trip the original, unrecognized `type` string and any companion fields as-is.
+            Self::Unknown { raw_type, extra } => UnknownVariant {
+                r#type: raw_type,
+                extra,
             }
             .serialize(serializer),
         }
@@ -195,53 +501,238 @@ impl<'de> Deserialize<'de> for NetworkProvider {
         D: serde::Deserializer<'de>,
     {
         #[derive(Deserialize)]
-        #[serde(deny_unknown_fields, untagged)]
+        #[serde(untagged)]
         enum ShorthandOrTagged {
-            Shorthand(#[serde(deserialize_with = "deserialize_url")] Url),
+            Shorthand(String),
+            // The tagged form is deserialized through `Tagged`'s own `Visitor`/`MapAccess`
+            // implementation below, since an unrecognized `type` must be routed into
+            // `NetworkProvider::Unknown` instead of failing the whole map.
             Tagged(Tagged),
         }
 
-        #[derive(Deserialize)]
-        #[serde(deny_unknown_fields, tag = "type", rename_all = "snake_case")]
         enum Tagged {
-            Rpc(RpcVariant),
-            Free(FreeVariant),
+            Rpc(String),
+            Free {
+                vendor: FreeProviderVendor,
+                api_key: Option<String>,
+            },
+            Unknown {
+                raw_type: String,
+                extra: IndexMap<String, toml::Value>,
+            },
         }
 
-        #[derive(Deserialize)]
-        #[serde(deny_unknown_fields, transparent)]
-        struct RpcVariant {
-            #[serde(deserialize_with = "deserialize_url")]
-            url: Url,
+        impl<'de> Deserialize<'de> for Tagged {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                deserializer.deserialize_map(TaggedVisitor)
+            }
         }
 
-        #[derive(Deserialize)]
-        #[serde(deny_unknown_fields)]
-        struct FreeVariant {
-            vendor: FreeProviderVendor,
+        struct TaggedVisitor;
+
+        impl<'de> Visitor<'de> for TaggedVisitor {
+            type Value = Tagged;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                write!(formatter, "a provider table with a `type` key")
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where
+                A: serde::de::MapAccess<'de>,
+            {
+                let mut raw_type: Option<String> = None;
+                // Known keys are kept as raw values until `type` is known, so that an `Unknown`
+                // provider can keep them as-is in `extra`.
+                let mut url: Option<toml::Value> = None;
+                let mut vendor: Option<toml::Value> = None;
+                let mut api_key: Option<toml::Value> = None;
+                // Fields that aren't one of the known keys above. These are only an error once we
+                // know `type` resolved to a provider this build actually understands — for an
+                // `Unknown` provider they're preserved instead, so a newer provider's own fields
+                // (e.g. a hypothetical `websocket` provider's `endpoint`/`timeout_ms`) don't make
+                // the whole config fail to load.
+                let mut extra = IndexMap::new();
+
+                while let Some(key) = map.next_key::<String>()? {
+                    match key.as_str() {
+                        "type" => {
+                            if raw_type.is_some() {
+                                return Err(serde::de::Error::duplicate_field("type"));
+                            }
+                            raw_type = Some(map.next_value()?);
+                        }
+                        "url" => {
+                            if url.is_some() {
+                                return Err(serde::de::Error::duplicate_field("url"));
+                            }
+                            url = Some(map.next_value()?);
+                        }
+                        "vendor" => {
+                            if vendor.is_some() {
+                                return Err(serde::de::Error::duplicate_field("vendor"));
+                            }
+                            vendor = Some(map.next_value()?);
+                        }
+                        "api_key" => {
+                            if api_key.is_some() {
+                                return Err(serde::de::Error::duplicate_field("api_key"));
+                            }
+                            api_key = Some(map.next_value()?);
+                        }
+                        other => {
+                            if extra.insert(other.to_owned(), map.next_value()?).is_some() {
+                                return Err(serde::de::Error::duplicate_field(
+                                    "<unrecognized field>",
+                                ));
+                            }
+                        }
+                    }
+                }
+
+                let raw_type =
+                    raw_type.ok_or_else(|| serde::de::Error::missing_field("type"))?;
+
+                Ok(match raw_type.as_str() {
+                    "rpc" if extra.is_empty() => Tagged::Rpc(
+                        url.ok_or_else(|| serde::de::Error::missing_field("url"))?
+                            .try_into()
+                            .map_err(serde::de::Error::custom)?,
+                    ),
+                    "free" if extra.is_empty() => Tagged::Free {
+                        vendor: vendor
+                            .ok_or_else(|| serde::de::Error::missing_field("vendor"))?
+                            .try_into()
+                            .map_err(serde::de::Error::custom)?,
+                        api_key: api_key
+                            .map(|value| value.try_into())
+                            .transpose()
+                            .map_err(serde::de::Error::custom)?,
+                    },
+                    "rpc" | "free" => {
+                        return Err(serde::de::Error::unknown_field(
+                            extra
+                                .keys()
+                                .next()
+                                .expect("non-empty `extra` has a first key"),
+                            &["type", "url", "vendor", "api_key"],
+                        ));
+                    }
+                    _ => {
+                        let mut extra = extra;
+                        for (key, value) in [("url", url), ("vendor", vendor), ("api_key", api_key)] {
+                            if let Some(value) = value {
+                                extra.insert(key.to_owned(), value);
+                            }
+                        }
+                        Tagged::Unknown { raw_type, extra }
+                    }
+                })
+            }
         }
 
         Ok(match ShorthandOrTagged::deserialize(deserializer)? {
             ShorthandOrTagged::Shorthand(value) => Self::Rpc(value),
             ShorthandOrTagged::Tagged(value) => match value {
-                Tagged::Rpc(value) => Self::Rpc(value.url),
-                Tagged::Free(value) => Self::Free(value.vendor),
+                Tagged::Rpc(url) => Self::Rpc(url),
+                Tagged::Free { vendor, api_key } => Self::Free { vendor, api_key },
+                Tagged::Unknown { raw_type, extra } => Self::Unknown { raw_type, extra },
             },
         })
     }
 }
 
+impl NetworkProvider {
+    /// Expands `${VAR}` / `${VAR:-default}` placeholders against the process environment and
+    /// turns the result into the concrete, authenticated endpoint to connect to. This is
+    /// deliberately not run at deserialize time, so `profiles.toml` never needs to contain a
+    /// secret.
+    pub fn resolve(&self) -> Result<ResolvedProvider> {
+        match self {
+            Self::Rpc(url) => Ok(ResolvedProvider {
+                url: Url::parse(&interpolate_env(url)?)?,
+            }),
+            Self::Free { vendor, api_key } => {
+                let api_key = match api_key {
+                    Some(raw_api_key) => interpolate_env(raw_api_key)?,
+                    None => anyhow::bail!(
+                        "free provider `{}` requires an `api_key` to be configured",
+                        vendor
+                    ),
+                };
+
+                Ok(ResolvedProvider {
+                    url: vendor.endpoint_url(&api_key)?,
+                })
+            }
+            Self::Unknown { raw_type, .. } => anyhow::bail!(
+                "network uses provider type `{}`, which this version of starkli doesn't know \
+                 how to use; consider upgrading starkli",
+                raw_type
+            ),
+        }
+    }
+}
+
+impl FreeProviderVendor {
+    fn endpoint_url(&self, api_key: &str) -> Result<Url> {
+        let base = match self {
+            Self::Blast => "https://starknet-mainnet.blastapi.io",
+            Self::Nethermind => "https://rpc.nethermind.io/mainnet-juno",
+            Self::Unknown(raw_vendor) => anyhow::bail!(
+                "free provider vendor `{}` is not known to this version of starkli; consider \
+                 upgrading starkli",
+                raw_vendor
+            ),
+        };
+
+        let mut url = Url::parse(base).expect("hardcoded provider base URL must be valid");
+        url.path_segments_mut()
+            .map_err(|_| anyhow::anyhow!("provider base URL `{}` cannot be a base", base))?
+            .push(api_key);
+
+        Ok(url)
+    }
+}
+
 impl Display for FreeProviderVendor {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::Blast => write!(f, "Blast"),
             Self::Nethermind => write!(f, "Nethermind"),
+            Self::Unknown(raw_vendor) => write!(f, "{}", raw_vendor),
         }
     }
 }
 
-impl<'de> Visitor<'de> for ChainIdVisitor {
-    type Value = FieldElement;
+impl Serialize for FreeProviderVendor {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            Self::Blast => serializer.serialize_str("blast"),
+            Self::Nethermind => serializer.serialize_str("nethermind"),
+            // Round-trip the original, unrecognized vendor string as-is.
+            Self::Unknown(raw_vendor) => serializer.serialize_str(raw_vendor),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for FreeProviderVendor {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_str(FreeProviderVendorVisitor)
+    }
+}
+
+impl<'de> Visitor<'de> for FreeProviderVendorVisitor {
+    type Value = FreeProviderVendor;
 
     fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
         write!(formatter, "string")
@@ -251,17 +742,16 @@ impl<'de> Visitor<'de> for ChainIdVisitor {
     where
         E: serde::de::Error,
     {
-        cairo_short_string_to_felt(v).map_err(|_| {
-            serde::de::Error::invalid_value(
-                serde::de::Unexpected::Str(v),
-                &"valid Cairo short string",
-            )
+        Ok(match v {
+            "blast" => FreeProviderVendor::Blast,
+            "nethermind" => FreeProviderVendor::Nethermind,
+            other => FreeProviderVendor::Unknown(other.to_owned()),
         })
     }
 }
 
-impl<'de> Visitor<'de> for UrlVisitor {
-    type Value = Url;
+impl<'de> Visitor<'de> for ChainIdVisitor {
+    type Value = FieldElement;
 
     fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
         write!(formatter, "string")
@@ -271,8 +761,11 @@ impl<'de> Visitor<'de> for UrlVisitor {
     where
         E: serde::de::Error,
     {
-        Url::parse(v).map_err(|_| {
-            serde::de::Error::invalid_value(serde::de::Unexpected::Str(v), &"valid URL")
+        cairo_short_string_to_felt(v).map_err(|_| {
+            serde::de::Error::invalid_value(
+                serde::de::Unexpected::Str(v),
+                &"valid Cairo short string",
+            )
         })
     }
 }
@@ -294,13 +787,400 @@ where
     deserializer.deserialize_str(ChainIdVisitor)
 }
 
-fn deserialize_url<'de, D>(deserializer: D) -> Result<Url, D::Error>
-where
-    D: serde::Deserializer<'de>,
-{
-    deserializer.deserialize_str(UrlVisitor)
-}
-
 fn is_false(value: &bool) -> bool {
     value == &false
 }
+
+/// Expands `${VAR}` and `${VAR:-default}` placeholders in `input` against the process
+/// environment. Errors with the offending variable name if a referenced variable without a
+/// default is not set.
+fn interpolate_env(input: &str) -> Result<String> {
+    let mut output = String::with_capacity(input.len());
+    let mut rest = input;
+
+    while let Some(start) = rest.find("${") {
+        output.push_str(&rest[..start]);
+
+        let after_open = &rest[start + 2..];
+        let close = after_open
+            .find('}')
+            .ok_or_else(|| anyhow::anyhow!("unterminated `${{...}}` placeholder in `{}`", input))?;
+        let placeholder = &after_open[..close];
+
+        let (var_name, default) = match placeholder.split_once(":-") {
+            Some((var_name, default)) => (var_name, Some(default)),
+            None => (placeholder, None),
+        };
+
+        match std::env::var(var_name) {
+            Ok(value) => output.push_str(&value),
+            Err(_) => match default {
+                Some(default) => output.push_str(default),
+                None => anyhow::bail!(
+                    "environment variable `{}` is not set, and no default was provided",
+                    var_name
+                ),
+            },
+        }
+
+        rest = &after_open[close + 1..];
+    }
+    output.push_str(rest);
+
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rpc_network(chain_id: &str, url: &str) -> Network {
+        Network {
+            name: None,
+            chain_id: cairo_short_string_to_felt(chain_id).unwrap(),
+            is_integration: false,
+            provider: NetworkProvider::Rpc(url.to_owned()),
+        }
+    }
+
+    #[test]
+    fn extends_merges_and_overrides_parent_networks() {
+        let mut profiles = Profiles::default();
+        profiles
+            .profiles
+            .entry("default".to_owned())
+            .or_default()
+            .networks
+            .insert("mainnet".to_owned(), rpc_network("SN_MAIN", "http://parent"));
+        profiles
+            .profiles
+            .entry("default".to_owned())
+            .or_default()
+            .networks
+            .insert("sepolia".to_owned(), rpc_network("SN_SEPOLIA", "http://parent-sepolia"));
+
+        let mut child = Profile {
+            extends: Some("default".to_owned()),
+            networks: IndexMap::new(),
+        };
+        child
+            .networks
+            .insert("mainnet".to_owned(), rpc_network("SN_MAIN", "http://child"));
+        profiles.profiles.insert("staging".to_owned(), child);
+
+        let resolved = profiles.get_active(Some("staging")).unwrap();
+
+        assert_eq!(resolved.networks.len(), 2);
+        match &resolved.networks["mainnet"].provider {
+            NetworkProvider::Rpc(url) => assert_eq!(url, "http://child"),
+            other => panic!("expected Rpc, got {other:?}"),
+        }
+        match &resolved.networks["sepolia"].provider {
+            NetworkProvider::Rpc(url) => assert_eq!(url, "http://parent-sepolia"),
+            other => panic!("expected Rpc, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn extends_cycle_is_rejected() {
+        let mut profiles = Profiles::default();
+        profiles.profiles.insert(
+            "a".to_owned(),
+            Profile {
+                extends: Some("b".to_owned()),
+                networks: IndexMap::new(),
+            },
+        );
+        profiles.profiles.insert(
+            "b".to_owned(),
+            Profile {
+                extends: Some("a".to_owned()),
+                networks: IndexMap::new(),
+            },
+        );
+
+        let err = profiles.get_active(Some("a")).unwrap_err();
+        assert!(
+            err.to_string().contains("cycle"),
+            "expected a cycle error, got: {err}"
+        );
+    }
+
+    #[test]
+    fn interpolate_env_passes_through_plain_text() {
+        assert_eq!(interpolate_env("https://example.com/v1").unwrap(), "https://example.com/v1");
+    }
+
+    #[test]
+    fn interpolate_env_expands_existing_var() {
+        // Relies on `PATH` being set in the test environment rather than mutating process env,
+        // since setting env vars from tests is both unsafe (as of newer Rust editions) and racy
+        // across parallel test threads.
+        let path = std::env::var("PATH").expect("PATH should be set while running tests");
+        let expanded = interpolate_env("prefix/${PATH}/suffix").unwrap();
+        assert_eq!(expanded, format!("prefix/{path}/suffix"));
+    }
+
+    #[test]
+    fn interpolate_env_uses_default_when_var_unset() {
+        let expanded =
+            interpolate_env("${STARKLI_TEST_DOES_NOT_EXIST_XYZ:-fallback}").unwrap();
+        assert_eq!(expanded, "fallback");
+    }
+
+    #[test]
+    fn interpolate_env_errors_when_var_unset_without_default() {
+        let err = interpolate_env("${STARKLI_TEST_DOES_NOT_EXIST_XYZ}").unwrap_err();
+        assert!(
+            err.to_string().contains("STARKLI_TEST_DOES_NOT_EXIST_XYZ"),
+            "expected the error to name the missing variable, got: {err}"
+        );
+    }
+
+    #[test]
+    fn interpolate_env_errors_on_unterminated_placeholder() {
+        assert!(interpolate_env("${UNCLOSED").is_err());
+    }
+
+    #[test]
+    fn extends_missing_parent_is_rejected() {
+        let mut profiles = Profiles::default();
+        profiles.profiles.insert(
+            "child".to_owned(),
+            Profile {
+                extends: Some("does-not-exist".to_owned()),
+                networks: IndexMap::new(),
+            },
+        );
+
+        assert!(profiles.get_active(Some("child")).is_err());
+    }
+
+    #[test]
+    fn upsert_network_inserts_into_new_profile() {
+        let mut profiles = Profiles::default();
+        profiles
+            .upsert_network("default", "mainnet", rpc_network("SN_MAIN", "http://a"))
+            .unwrap();
+
+        assert_eq!(profiles.profiles["default"].networks.len(), 1);
+    }
+
+    #[test]
+    fn upsert_network_rejects_duplicate_chain_id_without_mutating_state() {
+        let mut profiles = Profiles::default();
+        profiles
+            .upsert_network("default", "n1", rpc_network("SN_MAIN", "http://a"))
+            .unwrap();
+        let before = format!("{:?}", profiles.profiles);
+
+        let err = profiles
+            .upsert_network("default", "n2", rpc_network("SN_MAIN", "http://b"))
+            .unwrap_err();
+        assert!(err.to_string().contains("same chain ID"));
+
+        let after = format!("{:?}", profiles.profiles);
+        assert_eq!(before, after, "a rejected upsert must leave `self` unchanged");
+    }
+
+    #[test]
+    fn upsert_network_rejects_duplicate_chain_id_keeping_existing_networks_of_profile() {
+        let mut profiles = Profiles::default();
+
+        profiles
+            .upsert_network("other", "n1", rpc_network("SN_MAIN", "http://a"))
+            .unwrap();
+        let err = profiles
+            .upsert_network("other", "n2", rpc_network("SN_MAIN", "http://b"))
+            .unwrap_err();
+        assert!(err.to_string().contains("same chain ID"));
+
+        assert_eq!(profiles.profiles["other"].networks.len(), 1);
+        assert!(profiles.profiles["other"].networks.contains_key("n1"));
+    }
+
+    #[test]
+    fn remove_network_then_rename_network_roundtrip() {
+        let mut profiles = Profiles::default();
+        profiles
+            .upsert_network("default", "mainnet", rpc_network("SN_MAIN", "http://a"))
+            .unwrap();
+        profiles
+            .upsert_network("default", "sepolia", rpc_network("SN_SEPOLIA", "http://b"))
+            .unwrap();
+
+        profiles
+            .rename_network("default", "mainnet", "mainnet-renamed")
+            .unwrap();
+        assert!(!profiles.profiles["default"].networks.contains_key("mainnet"));
+        assert!(profiles.profiles["default"]
+            .networks
+            .contains_key("mainnet-renamed"));
+
+        let removed = profiles.remove_network("default", "sepolia").unwrap();
+        match removed.provider {
+            NetworkProvider::Rpc(url) => assert_eq!(url, "http://b"),
+            other => panic!("expected Rpc, got {other:?}"),
+        }
+        assert!(!profiles.profiles["default"].networks.contains_key("sepolia"));
+    }
+
+    #[test]
+    fn set_active_profile_rejects_unknown_name() {
+        let mut profiles = Profiles::default();
+        assert!(profiles
+            .set_active_profile(Some("does-not-exist".to_owned()))
+            .is_err());
+        assert_eq!(profiles.active_profile, None);
+    }
+
+    #[test]
+    fn upsert_network_rejects_duplicate_chain_id_introduced_in_child_profile() {
+        let mut profiles = Profiles::default();
+        profiles
+            .upsert_network("parent", "a", rpc_network("SN_MAIN", "http://parent-a"))
+            .unwrap();
+        profiles.profiles.insert(
+            "child".to_owned(),
+            Profile {
+                extends: Some("parent".to_owned()),
+                networks: IndexMap::new(),
+            },
+        );
+        profiles
+            .upsert_network("child", "b", rpc_network("SN_SEPOLIA", "http://child-b"))
+            .unwrap();
+        let before = format!("{:?}", profiles.profiles);
+
+        // `c` doesn't collide with anything directly on `parent`, but collides with `child`'s
+        // own network `b` once `child`'s `extends = "parent"` is resolved.
+        let err = profiles
+            .upsert_network("parent", "c", rpc_network("SN_SEPOLIA", "http://parent-c"))
+            .unwrap_err();
+        assert!(err.to_string().contains("same chain ID"));
+
+        let after = format!("{:?}", profiles.profiles);
+        assert_eq!(
+            before, after,
+            "a rejected upsert must leave `self` unchanged, even when the collision is only \
+             visible in a profile that extends the one being mutated"
+        );
+
+        // Since the rejected upsert was rolled back, `child`'s resolved network set is
+        // unaffected and must still load cleanly.
+        assert!(profiles.get_active(Some("child")).is_ok());
+    }
+
+    #[test]
+    fn get_active_rejects_duplicate_chain_id_inherited_from_parent() {
+        // Bypasses `upsert_network` entirely, so this exercises `get_active`/`resolve_profile`'s
+        // own chain-ID validation rather than `upsert_network`'s rollback.
+        let mut profiles = Profiles::default();
+        let mut parent = Profile::default();
+        parent
+            .networks
+            .insert("a".to_owned(), rpc_network("SN_MAIN", "http://parent-a"));
+        profiles.profiles.insert("parent".to_owned(), parent);
+
+        let mut child = Profile {
+            extends: Some("parent".to_owned()),
+            networks: IndexMap::new(),
+        };
+        child
+            .networks
+            .insert("b".to_owned(), rpc_network("SN_MAIN", "http://child-b"));
+        profiles.profiles.insert("child".to_owned(), child);
+
+        let err = profiles.get_active(Some("child")).unwrap_err();
+        assert!(err.to_string().contains("same chain ID"));
+    }
+
+    #[test]
+    fn rename_network_to_its_own_name_is_a_no_op() {
+        let mut profiles = Profiles::default();
+        profiles
+            .upsert_network("default", "mainnet", rpc_network("SN_MAIN", "http://a"))
+            .unwrap();
+
+        profiles
+            .rename_network("default", "mainnet", "mainnet")
+            .unwrap();
+
+        assert!(profiles.profiles["default"]
+            .networks
+            .contains_key("mainnet"));
+    }
+
+    #[test]
+    fn rename_network_missing_network_still_errors() {
+        let mut profiles = Profiles::default();
+        profiles
+            .upsert_network("default", "mainnet", rpc_network("SN_MAIN", "http://a"))
+            .unwrap();
+
+        let err = profiles
+            .rename_network("default", "does-not-exist", "does-not-exist")
+            .unwrap_err();
+        assert!(err.to_string().contains("not found"));
+    }
+
+    #[test]
+    fn unknown_provider_type_round_trips_through_serialize() {
+        let toml_in = "type = \"websocket\"\nendpoint = \"wss://example.com\"\ntimeout_ms = 500\n";
+        let provider: NetworkProvider = toml::from_str(toml_in).unwrap();
+
+        match &provider {
+            NetworkProvider::Unknown { raw_type, extra } => {
+                assert_eq!(raw_type, "websocket");
+                assert_eq!(
+                    extra.get("endpoint").and_then(|v| v.as_str()),
+                    Some("wss://example.com")
+                );
+                assert_eq!(extra.get("timeout_ms").and_then(|v| v.as_integer()), Some(500));
+            }
+            other => panic!("expected Unknown, got {other:?}"),
+        }
+
+        let round_tripped = toml::to_string(&provider).unwrap();
+        let reparsed: NetworkProvider = toml::from_str(&round_tripped).unwrap();
+        match reparsed {
+            NetworkProvider::Unknown { raw_type, extra } => {
+                assert_eq!(raw_type, "websocket");
+                assert_eq!(
+                    extra.get("endpoint").and_then(|v| v.as_str()),
+                    Some("wss://example.com")
+                );
+                assert_eq!(extra.get("timeout_ms").and_then(|v| v.as_integer()), Some(500));
+            }
+            other => panic!("expected Unknown, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn unknown_free_provider_vendor_round_trips_through_serialize() {
+        let toml_in = "type = \"free\"\nvendor = \"quicknode\"\n";
+        let provider: NetworkProvider = toml::from_str(toml_in).unwrap();
+        match &provider {
+            NetworkProvider::Free { vendor, api_key } => {
+                assert_eq!(vendor.to_string(), "quicknode");
+                assert!(api_key.is_none());
+            }
+            other => panic!("expected Free, got {other:?}"),
+        }
+
+        let round_tripped = toml::to_string(&provider).unwrap();
+        assert!(round_tripped.contains("vendor = \"quicknode\""));
+    }
+
+    #[test]
+    fn unknown_provider_only_errors_on_resolve_not_on_deserialize() {
+        let toml_in = "type = \"websocket\"\nendpoint = \"wss://example.com\"\n";
+        let provider: NetworkProvider = toml::from_str(toml_in).unwrap();
+
+        let err = provider.resolve().unwrap_err();
+        assert!(
+            err.to_string().contains("websocket"),
+            "expected the error to name the unrecognized provider type, got: {err}"
+        );
+    }
+}